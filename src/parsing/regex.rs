@@ -2,7 +2,9 @@ use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize, Serializer, Deserializer};
 use serde_bytes::{Bytes, ByteBuf};
 use std::error::Error;
+use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::dumps::dump_to_uncompressed_binary;
 
@@ -16,7 +18,7 @@ use crate::dumps::dump_to_uncompressed_binary;
 pub struct Regex {
     source: Arc<RegexSource>,
     #[serde(skip)]
-    regex: OnceCell<regex_impl::Regex>,
+    regex: OnceCell<CompiledRegex>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -24,12 +26,36 @@ enum RegexSource {
     Pattern(String),
     Binary(Vec<u8>),
     ExprTree(fancy_regex::ExprTree),
+    /// A fully compiled, directly-deserializable automaton, produced by
+    /// [`Regex::compile_dfa`] for patterns that stay within a DFA-expressible
+    /// subset (no backreferences or lookaround). Loading one of these needs
+    /// no recompilation, which is what makes initializing a syntax set with
+    /// many of these dumped regexes fast.
+    Dfa(Vec<u8>),
+}
+
+/// The compiled form backing a [`Regex`]: either the normal backend engine,
+/// or a precompiled DFA loaded straight from a [`RegexSource::Dfa`] dump.
+#[derive(Debug)]
+enum CompiledRegex {
+    Backend(regex_impl::Regex),
+    /// A DFA can only report whether the whole pattern matched and where, not
+    /// the positions of any capture groups within it.
+    Dfa(regex_automata::dfa::regex::Regex),
 }
 
 /// A region contains text positions for capture groups in a match result.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Region {
-    region: regex_impl::Region,
+    region: RegionRepr,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum RegionRepr {
+    Backend(regex_impl::Region),
+    /// Populated by a [`CompiledRegex::Dfa`] search, which only ever reports
+    /// the whole match (group 0).
+    WholeMatch(Option<(usize, usize)>),
 }
 
 impl Regex {
@@ -70,13 +96,80 @@ impl Regex {
         match self.source.as_ref() {
             RegexSource::Pattern(pattern) => regex_impl::Regex::parse_expr_tree(pattern),
             RegexSource::Binary(binary) => regex_impl::Regex::deserialize_expr_tree(binary),
-            RegexSource::ExprTree(tree) => Ok(tree.clone())
+            RegexSource::ExprTree(tree) => Ok(tree.clone()),
+            RegexSource::Dfa(_) => Err("cannot recover an expression tree from a compiled DFA dump".into()),
+        }
+    }
+
+    /// Attempt to compile this regex into a precompiled DFA dump (see
+    /// [`RegexSource::Dfa`]), returning a regex that loads near-instantly
+    /// from its serialized form.
+    ///
+    /// Only patterns that stay within a DFA-expressible subset of the syntax
+    /// (no backreferences, lookaround, or capturing groups — a DFA can only
+    /// ever report where the whole match starts and ends) can be compiled
+    /// this way, and only when `self` is a plain [`RegexSource::Pattern`]:
+    /// `regex_automata` needs the original pattern text to build from, and an
+    /// already-parsed [`fancy_regex::ExprTree`] has none to give it. For
+    /// anything else, this returns a clone of `self` unchanged, so that the
+    /// normal backend and its lazy compilation keep handling it.
+    pub fn compile_dfa(&self) -> Self {
+        match self.try_compile_dfa() {
+            Some(dfa) => Self {
+                source: Arc::new(RegexSource::Dfa(dfa)),
+                regex: OnceCell::new(),
+            },
+            None => self.clone(),
+        }
+    }
+
+    fn try_compile_dfa(&self) -> Option<Vec<u8>> {
+        let RegexSource::Pattern(pattern) = self.source.as_ref() else {
+            return None;
+        };
+        let tree = regex_impl::Regex::parse_expr_tree(pattern).ok()?;
+        if !Self::is_dfa_expressible(&tree.expr) {
+            return None;
+        }
+
+        // A `dfa::regex::Regex` needs both the forward automaton (to find
+        // where a match ends) and the reverse one (to find where it starts)
+        // to report a match at all, so both have to survive the round trip
+        // through a dump. They're stored back to back, forward first, with a
+        // length prefix so `regex()` can split them apart again.
+        let dfa = regex_automata::dfa::regex::Regex::new(pattern).ok()?;
+        let (forward, _) = dfa.forward().to_bytes_little_endian();
+        let (reverse, _) = dfa.reverse().to_bytes_little_endian();
+
+        let mut dump = Vec::with_capacity(8 + forward.len() + reverse.len());
+        dump.extend_from_slice(&(forward.len() as u64).to_le_bytes());
+        dump.extend_from_slice(&forward);
+        dump.extend_from_slice(&reverse);
+        Some(dump)
+    }
+
+    /// Whether `expr` (and everything it contains) avoids the constructs a
+    /// DFA can't represent: backreferences, lookaround, and capturing groups
+    /// (a DFA-backed [`Region`] can only ever report the whole match, group
+    /// 0, so a pattern relying on numbered groups can't be routed through
+    /// one without silently losing them).
+    fn is_dfa_expressible(expr: &fancy_regex::Expr) -> bool {
+        use fancy_regex::Expr;
+
+        match expr {
+            Expr::Backref(_) | Expr::LookAround(..) | Expr::Group(_) => false,
+            Expr::Concat(subs) | Expr::Alt(subs) => subs.iter().all(Self::is_dfa_expressible),
+            Expr::Repeat { child, .. } => Self::is_dfa_expressible(child),
+            _ => true,
         }
     }
 
     /// Check if the regex matches the given text.
     pub fn is_match(&self, text: &str) -> bool {
-        self.regex().is_match(text)
+        match self.regex() {
+            CompiledRegex::Backend(regex) => regex.is_match(text),
+            CompiledRegex::Dfa(dfa) => dfa.is_match(text),
+        }
     }
 
     /// Search for the pattern in the given text from begin/end positions.
@@ -93,15 +186,160 @@ impl Regex {
         end: usize,
         region: Option<&mut Region>,
     ) -> bool {
-        self.regex()
-            .search(text, begin, end, region.map(|r| &mut r.region))
+        match self.regex() {
+            CompiledRegex::Backend(regex) => regex.search(text, begin, end, region.map(Region::as_backend_mut)),
+            CompiledRegex::Dfa(dfa) => Self::search_dfa(dfa, text, begin, end, region),
+        }
     }
 
-    fn regex(&self) -> &regex_impl::Regex {
-        self.regex.get_or_init(|| {
-            regex_impl::Regex::from_expr_tree(self.expr_tree().expect("regex string should be pre-tested"))
+    /// Search for the pattern like [`Regex::search`], but under the given
+    /// [`MatchConfig`], surfacing catastrophic backtracking instead of
+    /// silently treating it as "no match".
+    ///
+    /// Returns `Ok(true)`/`Ok(false)` for a definite match/no-match, or
+    /// `Err(MatchError)` if the search hit `retry_limit` or
+    /// `time_limit` before it could decide either way.
+    pub fn search_with_config(
+        &self,
+        text: &str,
+        begin: usize,
+        end: usize,
+        region: Option<&mut Region>,
+        config: &MatchConfig,
+    ) -> Result<bool, MatchError> {
+        match self.regex() {
+            CompiledRegex::Backend(regex) => {
+                regex.search_with_config(text, begin, end, region.map(Region::as_backend_mut), config)
+            }
+            // A DFA's search time is bounded by construction, so there's
+            // nothing for `retry_limit`/`time_limit` to abort.
+            CompiledRegex::Dfa(dfa) => Ok(Self::search_dfa(dfa, text, begin, end, region)),
+        }
+    }
+
+    /// Search for the pattern like [`Regex::search`], but over raw bytes
+    /// instead of a `&str`, so that input which isn't valid UTF-8 (Latin-1
+    /// logs, mixed-encoding source) can be highlighted without a lossy
+    /// conversion that would shift every offset.
+    ///
+    /// `begin`/`end` and the positions recorded in `region` are all byte
+    /// offsets into `text`.
+    pub fn search_bytes(
+        &self,
+        text: &[u8],
+        begin: usize,
+        end: usize,
+        region: Option<&mut Region>,
+    ) -> bool {
+        match self.regex() {
+            CompiledRegex::Backend(regex) => regex.search_bytes(text, begin, end, region.map(Region::as_backend_mut)),
+            CompiledRegex::Dfa(dfa) => {
+                // A DFA operates over raw bytes regardless of UTF-8 validity,
+                // so this doesn't need the lossy-conversion fallback the
+                // fancy-regex backend needs.
+                let matched = dfa.find(&text[begin..end]);
+                Self::store_whole_match(region, matched.map(|m| (begin + m.start(), begin + m.end())));
+                matched.is_some()
+            }
+        }
+    }
+
+    fn search_dfa(
+        dfa: &regex_automata::dfa::regex::Regex,
+        text: &str,
+        begin: usize,
+        end: usize,
+        region: Option<&mut Region>,
+    ) -> bool {
+        let matched = dfa.find(&text.as_bytes()[begin..end]);
+        Self::store_whole_match(region, matched.map(|m| (begin + m.start(), begin + m.end())));
+        matched.is_some()
+    }
+
+    fn store_whole_match(region: Option<&mut Region>, pos: Option<(usize, usize)>) {
+        if let Some(region) = region {
+            region.region = RegionRepr::WholeMatch(pos);
+        }
+    }
+
+    fn regex(&self) -> &CompiledRegex {
+        self.regex.get_or_init(|| match self.source.as_ref() {
+            RegexSource::Dfa(dump) => {
+                let (forward_len_bytes, rest) = dump.split_at(8);
+                let forward_len = u64::from_le_bytes(
+                    forward_len_bytes.try_into().expect("length prefix should be 8 bytes"),
+                ) as usize;
+                let (forward_bytes, reverse_bytes) = rest.split_at(forward_len);
+
+                let forward = regex_automata::dfa::dense::DFA::from_bytes(forward_bytes)
+                    .expect("DFA dump should have been produced by Regex::compile_dfa")
+                    .0
+                    .to_owned();
+                let reverse = regex_automata::dfa::dense::DFA::from_bytes(reverse_bytes)
+                    .expect("DFA dump should have been produced by Regex::compile_dfa")
+                    .0
+                    .to_owned();
+
+                CompiledRegex::Dfa(regex_automata::dfa::regex::Builder::new().build_from_dfas(forward, reverse))
+            }
+            // Go straight from pattern text when we have it, rather than
+            // through an expression tree: not every backend (PCRE2) can
+            // recompile from a bare `fancy_regex::ExprTree`, which has no
+            // stored source text or pretty-printer to recover one from.
+            RegexSource::Pattern(pattern) => CompiledRegex::Backend(
+                regex_impl::Regex::new(pattern).expect("regex string should be pre-tested"),
+            ),
+            _ => CompiledRegex::Backend(regex_impl::Regex::from_expr_tree(
+                self.expr_tree().expect("regex string should be pre-tested"),
+            )),
         })
     }
+
+    /// Return the literal byte sequences that any match of this pattern must
+    /// start with, or `None` if the pattern can match starting with something
+    /// other than a fixed literal (an unanchored class, `.`, a lookaround,
+    /// and so on).
+    ///
+    /// This only looks at the leading `Concat`/`Literal`/`Alt` structure of
+    /// the expression tree, so it is a prefilter, not a full analysis: a
+    /// pattern this returns `None` for may still only ever match a handful of
+    /// literals, but a pattern this returns `Some` for is guaranteed to need
+    /// one of the returned literals at its match start.
+    pub fn required_prefix_literals(&self) -> Option<Vec<String>> {
+        let tree = self.expr_tree().ok()?;
+        Self::leading_literals(&tree.expr)
+    }
+
+    fn leading_literals(expr: &fancy_regex::Expr) -> Option<Vec<String>> {
+        use fancy_regex::Expr;
+
+        match expr {
+            Expr::Literal { val, casei: false } => Some(vec![val.clone()]),
+            Expr::Concat(subs) => {
+                let mut prefix = String::new();
+                for sub in subs {
+                    match sub {
+                        Expr::Literal { val, casei: false } => prefix.push_str(val),
+                        _ => break,
+                    }
+                }
+                if prefix.is_empty() {
+                    None
+                } else {
+                    Some(vec![prefix])
+                }
+            }
+            Expr::Alt(subs) => {
+                let mut prefixes = Vec::with_capacity(subs.len());
+                for sub in subs {
+                    prefixes.extend(Self::leading_literals(sub)?);
+                }
+                Some(prefixes)
+            }
+            Expr::Group(inner) => Self::leading_literals(inner),
+            _ => None,
+        }
+    }
 }
 
 impl Clone for Regex {
@@ -122,32 +360,61 @@ impl PartialEq for Regex {
 impl Eq for Regex {}
 
 
+/// Leading byte of a serialized [`RegexSource`], distinguishing a dumped
+/// `fancy_regex::ExprTree` (the format `RegexSource::Binary` is deserialized
+/// as) from a precompiled [`RegexSource::Dfa`] dump, since both are opaque
+/// byte blobs once serialized.
+const TAG_EXPR_TREE: u8 = 0;
+const TAG_DFA: u8 = 1;
+
 impl Serialize for RegexSource {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        match self {
-            RegexSource::Binary(binary) => Bytes::new(binary.as_slice()).serialize(serializer),
-            RegexSource::ExprTree(tree) => ByteBuf::from(dump_to_uncompressed_binary(tree)).serialize(serializer),
-            RegexSource::Pattern(pattern) => ByteBuf::from(dump_to_uncompressed_binary(&regex_impl::Regex::parse_expr_tree(pattern).map_err(|_| serde::ser::Error::custom("invalid regex"))?)).serialize(serializer)
-        }
+        let tagged = match self {
+            RegexSource::Binary(binary) => tag(TAG_EXPR_TREE, binary),
+            RegexSource::ExprTree(tree) => tag(TAG_EXPR_TREE, &dump_to_uncompressed_binary(tree)),
+            RegexSource::Pattern(pattern) => tag(
+                TAG_EXPR_TREE,
+                &dump_to_uncompressed_binary(
+                    &regex_impl::Regex::parse_expr_tree(pattern).map_err(|_| serde::ser::Error::custom("invalid regex"))?,
+                ),
+            ),
+            RegexSource::Dfa(dump) => tag(TAG_DFA, dump),
+        };
+        Bytes::new(&tagged).serialize(serializer)
     }
 }
 
+fn tag(tag: u8, bytes: &[u8]) -> Vec<u8> {
+    let mut tagged = Vec::with_capacity(bytes.len() + 1);
+    tagged.push(tag);
+    tagged.extend_from_slice(bytes);
+    tagged
+}
+
 impl<'de> Deserialize<'de> for RegexSource {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        Ok(RegexSource::Binary(ByteBuf::deserialize(deserializer)?.into_vec()))
+        let mut bytes = ByteBuf::deserialize(deserializer)?.into_vec();
+        if bytes.is_empty() {
+            return Err(serde::de::Error::custom("empty regex dump"));
+        }
+        let tag = bytes.remove(0);
+        match tag {
+            TAG_DFA => Ok(RegexSource::Dfa(bytes)),
+            _ => Ok(RegexSource::Binary(bytes)),
+        }
     }
 }
 
 impl Region {
     pub fn new() -> Self {
         Self {
-            region: regex_impl::new_region(),
+            region: RegionRepr::Backend(regex_impl::new_region()),
         }
     }
 
@@ -156,7 +423,29 @@ impl Region {
     /// If there is no match for that group or the index does not correspond to a group, `None` is
     /// returned. The index 0 returns the whole match.
     pub fn pos(&self, index: usize) -> Option<(usize, usize)> {
-        self.region.pos(index)
+        match &self.region {
+            RegionRepr::Backend(region) => region.pos(index),
+            RegionRepr::WholeMatch(pos) => {
+                if index == 0 {
+                    *pos
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Get a mutable reference to the backend-specific region, resetting it
+    /// to an empty one first if this `Region` was last populated by a
+    /// [`CompiledRegex::Dfa`] search.
+    fn as_backend_mut(&mut self) -> &mut regex_impl::Region {
+        if !matches!(self.region, RegionRepr::Backend(_)) {
+            self.region = RegionRepr::Backend(regex_impl::new_region());
+        }
+        match &mut self.region {
+            RegionRepr::Backend(region) => region,
+            RegionRepr::WholeMatch(_) => unreachable!(),
+        }
     }
 }
 
@@ -166,6 +455,331 @@ impl Default for Region {
     }
 }
 
+/// Limits placed on a single [`Regex::search_with_config`] call, so that
+/// pathological patterns can be aborted instead of silently reported as
+/// non-matching.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MatchConfig {
+    /// Maximum number of retries the backend's matcher may attempt before
+    /// giving up. Mapped onto onig's `MatchParam::retry_limit_in_match`;
+    /// ignored by the fancy-regex backend, which only understands wall time.
+    pub retry_limit: Option<usize>,
+    /// Maximum wall-clock time the search may take. Checked as a deadline by
+    /// the fancy-regex backend; ignored by onig, which only understands a
+    /// retry count.
+    pub time_limit: Option<Duration>,
+}
+
+/// The search was aborted because it exceeded a [`MatchConfig`] limit, rather
+/// than running to completion and finding no match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchError;
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "regex search aborted: exceeded configured match limit")
+    }
+}
+
+impl Error for MatchError {}
+
+/// A small, fixed-size pool of long-lived worker threads, used by backends
+/// (PCRE2, fancy-regex) that have no native way to interrupt a search in
+/// progress to bound `search_with_config`'s wall-clock time: the search runs
+/// as a job on the pool while the caller waits on a channel with a timeout,
+/// instead of spawning (and, on timeout, abandoning) a fresh OS thread per
+/// call. That would let a sustained pathological workload — exactly what
+/// `time_limit` exists to guard against — accumulate an unbounded number of
+/// runaway threads, and would add thread-spawn overhead to every timed
+/// search on what's meant to be the hot path of highlighting.
+mod timeout_pool {
+    use once_cell::sync::OnceCell;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    type Job = Box<dyn FnOnce() + Send + 'static>;
+
+    /// Workers are few and long-lived: a timed-out search isn't cancelled,
+    /// just abandoned by its caller, so it keeps occupying its worker until
+    /// it finishes on its own. A small, fixed count bounds how many such
+    /// abandoned searches can run at once, rather than leaving that
+    /// unbounded.
+    const WORKERS: usize = 4;
+
+    struct Pool {
+        jobs: mpsc::Sender<Job>,
+    }
+
+    fn spawn() -> Pool {
+        let (jobs, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..WORKERS {
+            let receiver = Arc::clone(&receiver);
+            std::thread::spawn(move || loop {
+                // Release the lock before running the job, so the other
+                // workers can pick up jobs while this one is busy.
+                let job = receiver.lock().unwrap().recv();
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => break,
+                }
+            });
+        }
+        Pool { jobs }
+    }
+
+    static POOL: OnceCell<Pool> = OnceCell::new();
+
+    /// Hand `job` off to the shared worker pool instead of spawning a new OS
+    /// thread for it.
+    pub fn run(job: impl FnOnce() + Send + 'static) {
+        let pool = POOL.get_or_init(spawn);
+        // If every worker is currently wedged on an abandoned pathological
+        // search, `job` just waits in the channel until one frees up; the
+        // caller's own `recv_timeout` still bounds how long it waits on the
+        // result.
+        let _ = pool.jobs.send(Box::new(job));
+    }
+}
+
+/// A set of patterns compiled into a single engine, so that a context's
+/// patterns can be tried in one search instead of one regex at a time.
+///
+/// Neither onig nor fancy-regex expose native set matching, so this is built
+/// on top of the normal regex engine: the member patterns are combined into
+/// one alternation, with each member wrapped in its own capture group so that,
+/// after a match, the participating wrapper group reveals which member fired.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RegexSet {
+    source: Arc<RegexSetSource>,
+    #[serde(skip)]
+    regex: OnceCell<Regex>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RegexSetSource {
+    patterns: Vec<String>,
+    /// A previously-dumped automaton for the combined pattern, mirroring
+    /// `RegexSource::Binary`: present once a set has gone through a
+    /// serialize/deserialize round trip, so the combined pattern doesn't
+    /// need to be re-parsed from its source text. `None` for a freshly
+    /// constructed set, which compiles the combined pattern lazily like
+    /// [`Regex::new`] does.
+    combined: Option<Vec<u8>>,
+}
+
+impl RegexSet {
+    /// Create a new regex set from the member patterns.
+    ///
+    /// Note that compilation happens on first use, mirroring [`Regex::new`].
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self {
+            source: Arc::new(RegexSetSource { patterns, combined: None }),
+            regex: OnceCell::new(),
+        }
+    }
+
+    /// Search for the leftmost match among all member patterns, starting
+    /// from `begin` and not looking past `end`.
+    ///
+    /// If a region is passed, it is used for storing match group positions,
+    /// the same way [`Regex::search`] reuses one, which makes a significant
+    /// performance difference in the hot per-position scan loop this type
+    /// exists for.
+    ///
+    /// Returns the index of the matching member pattern along with the
+    /// match's start/end positions, or `None` if no member matches.
+    pub fn search_leftmost(
+        &self,
+        text: &str,
+        begin: usize,
+        end: usize,
+        region: Option<&mut Region>,
+    ) -> Option<(usize, usize, usize)> {
+        let mut owned_region;
+        let region = match region {
+            Some(region) => region,
+            None => {
+                owned_region = Region::new();
+                &mut owned_region
+            }
+        };
+
+        if !self.regex().search(text, begin, end, Some(region)) {
+            return None;
+        }
+        for (index, wrapper_group) in self.wrapper_groups().enumerate() {
+            if let Some(pos) = region.pos(wrapper_group) {
+                return Some((index, pos.0, pos.1));
+            }
+        }
+        // The combined pattern matched, but no wrapper group participated; this
+        // should not happen since every alternative is itself a wrapper group.
+        region.pos(0).map(|(start, finish)| (0, start, finish))
+    }
+
+    fn regex(&self) -> &Regex {
+        self.regex.get_or_init(|| match &self.source.combined {
+            Some(dump) => Regex::deserialize(dump.clone()),
+            None => Regex::new(Self::combine(&self.source.patterns)),
+        })
+    }
+
+    /// Combine the member patterns into one alternation `(<p0>)|(<p1>)|…`,
+    /// wrapping each member in its own capture group so the participating
+    /// group can be inspected after a match via [`Region::pos`]. Each member's
+    /// own capture groups are left untouched, so later wrapper groups shift by
+    /// however many groups the preceding members contain.
+    fn combine(patterns: &[String]) -> String {
+        patterns
+            .iter()
+            .map(|pattern| format!("({})", pattern))
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    /// The group index of each member's wrapper group within the combined
+    /// pattern, in member order, accounting for capture groups nested inside
+    /// earlier members.
+    fn wrapper_groups(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut next_group = 1;
+        self.source.patterns.iter().map(move |pattern| {
+            let wrapper_group = next_group;
+            next_group += 1 + Self::count_capture_groups(pattern);
+            wrapper_group
+        })
+    }
+
+    /// Count the capture groups in a pattern, so that later wrapper groups
+    /// can be placed at the right index in the combined alternation. Plain
+    /// `(...)` groups and named groups (`(?<name>...)`, `(?P<name>...)`) are
+    /// counted; `(?:...)`, lookaround, and other `(?...)` constructs are not
+    /// capturing. A `(` inside a bracket expression like `[()]` is a literal,
+    /// not a group, and is skipped. A POSIX sub-expression nested inside a
+    /// bracket expression (`[:alpha:]`, `[.ch.]`, `[=a=]`) is also skipped
+    /// wholesale, so its own `]` doesn't get mistaken for the one closing the
+    /// outer class.
+    fn count_capture_groups(pattern: &str) -> usize {
+        let bytes = pattern.as_bytes();
+        let mut count = 0;
+        let mut i = 0;
+        let mut escaped = false;
+        let mut in_class = false;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if in_class {
+                if byte == b'[' && matches!(bytes.get(i + 1), Some(b':' | b'.' | b'=')) {
+                    i = Self::skip_posix_class(bytes, i);
+                } else if byte == b']' {
+                    in_class = false;
+                }
+            } else if byte == b'[' {
+                in_class = true;
+            } else if byte == b'(' {
+                if Self::is_capturing_group_start(&bytes[i..]) {
+                    count += 1;
+                }
+            }
+            i += 1;
+        }
+        count
+    }
+
+    /// Given `bytes[class_start]` is the `[` opening a POSIX bracket
+    /// sub-expression (`[:alpha:]`, `[.ch.]`, `[=a=]`), return the index of
+    /// its closing `]`, so the caller can skip straight past it. Returns the
+    /// index of the last byte in `bytes` if the sub-expression is never
+    /// closed.
+    fn skip_posix_class(bytes: &[u8], class_start: usize) -> usize {
+        let marker = bytes[class_start + 1];
+        let mut j = class_start + 2;
+        while j + 1 < bytes.len() && !(bytes[j] == marker && bytes[j + 1] == b']') {
+            j += 1;
+        }
+        (j + 1).min(bytes.len() - 1)
+    }
+
+    /// Whether the `(` at the start of `rest` opens a capturing group: either
+    /// a plain `(...)`, or a named group `(?<name>...)`/`(?P<name>...)` (but
+    /// not a lookbehind `(?<=...)`/`(?<!...)`, which also starts with `(?<`).
+    fn is_capturing_group_start(rest: &[u8]) -> bool {
+        match rest.get(1) {
+            Some(b'?') => match rest.get(2) {
+                Some(b'<') => !matches!(rest.get(3), Some(b'=') | Some(b'!')),
+                Some(b'P') => rest.get(3) == Some(&b'<'),
+                _ => false,
+            },
+            _ => true,
+        }
+    }
+}
+
+impl Clone for RegexSet {
+    fn clone(&self) -> Self {
+        RegexSet {
+            source: self.source.clone(),
+            regex: OnceCell::new(),
+        }
+    }
+}
+
+impl PartialEq for RegexSet {
+    fn eq(&self, other: &RegexSet) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for RegexSet {}
+
+/// The on-disk shape of a [`RegexSetSource`] dump: the member patterns (still
+/// needed to work out wrapper group indices) alongside a dump of the
+/// combined pattern's automaton, mirroring how [`RegexSource`] stores a
+/// dumped `fancy_regex::ExprTree` rather than re-parsing pattern text.
+#[derive(Serialize, Deserialize)]
+struct RegexSetDump<'a> {
+    patterns: std::borrow::Cow<'a, [String]>,
+    combined: ByteBuf,
+}
+
+impl Serialize for RegexSetSource {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let combined = match &self.combined {
+            Some(dump) => dump.clone(),
+            None => dump_to_uncompressed_binary(
+                &regex_impl::Regex::parse_expr_tree(&RegexSet::combine(&self.patterns))
+                    .map_err(|_| serde::ser::Error::custom("invalid regex"))?,
+            ),
+        };
+        RegexSetDump {
+            patterns: std::borrow::Cow::Borrowed(&self.patterns),
+            combined: ByteBuf::from(combined),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RegexSetSource {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let dump = RegexSetDump::deserialize(deserializer)?;
+        Ok(RegexSetSource {
+            patterns: dump.patterns.into_owned(),
+            combined: Some(dump.combined.into_vec()),
+        })
+    }
+}
+
 #[cfg(feature = "regex-onig")]
 mod regex_impl {
     pub use onig::Region;
@@ -221,21 +835,249 @@ mod regex_impl {
             // fail with a "retry-limit-in-match over" error eventually.
             matches!(matched, Ok(Some(_)))
         }
+
+        pub fn search_with_config(
+            &self,
+            text: &str,
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+            config: &super::MatchConfig,
+        ) -> Result<bool, super::MatchError> {
+            let mut match_param = MatchParam::default();
+            if let Some(retry_limit) = config.retry_limit {
+                match_param = match_param.retry_limit_in_match(retry_limit);
+            }
+
+            let matched = self.regex.search_with_param(
+                text,
+                begin,
+                end,
+                SearchOptions::SEARCH_OPTION_NONE,
+                region,
+                match_param,
+            );
+
+            match matched {
+                Ok(Some(_)) => Ok(true),
+                Ok(None) => Ok(false),
+                // onig reports catastrophic backtracking as a "retry-limit-in-match
+                // over" error once `retry_limit` is hit; anything else is an error
+                // we don't expect, so treat it the same way rather than panicking.
+                Err(_) => Err(super::MatchError),
+            }
+        }
+
+        pub fn search_bytes(
+            &self,
+            text: &[u8],
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+        ) -> bool {
+            // `text` is explicitly allowed to be invalid UTF-8, so fabricating
+            // a `&str` out of it (even just for typing) would be unsound.
+            // Search the raw bytes directly instead, via onig's own
+            // byte-oriented encoding support.
+            let matched = self.regex.search_with_encoding(
+                onig::EncodedBytes::ascii(text),
+                begin,
+                end,
+                SearchOptions::SEARCH_OPTION_NONE,
+                region,
+            );
+
+            matches!(matched, Some(_))
+        }
+    }
+}
+
+// If regex-onig is also requested, this condition makes regex-onig win.
+#[cfg(all(feature = "regex-pcre2", not(feature = "regex-onig")))]
+mod regex_impl {
+    use std::error::Error;
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
+    use pcre2::bytes::{Regex as Pcre2Regex, RegexBuilder};
+
+    use crate::dumps::from_uncompressed_data;
+
+    #[derive(Debug)]
+    pub struct Regex {
+        // `Arc`, not an owned `Pcre2Regex`, so `search_with_config` can hand
+        // a cheap clone off to the shared worker pool when enforcing
+        // `time_limit` (see its doc comment below).
+        regex: Arc<Pcre2Regex>,
+    }
+
+    #[derive(Clone, Debug, Default, Eq, PartialEq)]
+    pub struct Region {
+        positions: Vec<Option<(usize, usize)>>,
+    }
+
+    pub fn new_region() -> Region {
+        Region { positions: Vec::new() }
+    }
+
+    impl Regex {
+        pub fn new(pattern: &str) -> Result<Regex, Box<dyn Error + Send + Sync + 'static>> {
+            let regex = RegexBuilder::new().utf(true).ucp(true).jit_if_available(true).build(pattern)?;
+            Ok(Regex { regex: Arc::new(regex) })
+        }
+
+        pub fn parse_expr_tree(pattern: &str) -> Result<fancy_regex::ExprTree, Box<dyn Error + Send + Sync + 'static>> {
+            Ok(fancy_regex::Expr::parse_tree(pattern)?)
+        }
+
+        pub fn deserialize_expr_tree(binary: &[u8]) -> Result<fancy_regex::ExprTree, Box<dyn Error + Send + Sync + 'static>> {
+            Ok(from_uncompressed_data(binary)?)
+        }
+
+        pub fn from_expr_tree(_expr_tree: fancy_regex::ExprTree) -> Regex {
+            // `fancy_regex::ExprTree` stores only the parsed AST (`expr`,
+            // `backrefs`, `named_groups`), with no source text or
+            // pretty-printer to recover one from, and PCRE2 can only compile
+            // from pattern text. A `RegexSource::Pattern`-backed regex never
+            // reaches this path (see `Regex::regex`'s dispatch, which calls
+            // `Regex::new` with the original pattern directly); only a
+            // `RegexSource::Binary`/`ExprTree`-backed one can, which this
+            // backend has no way to support.
+            unimplemented!("PCRE2 backend cannot recompile a regex from an expression tree alone")
+        }
+
+        pub fn is_match(&self, text: &str) -> bool {
+            // Errors are treated as non-matches, same as the other backends.
+            self.regex.is_match(text.as_bytes()).unwrap_or(false)
+        }
+
+        pub fn search(
+            &self,
+            text: &str,
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+        ) -> bool {
+            self.search_bytes(text.as_bytes(), begin, end, region)
+        }
+
+        pub fn search_bytes(
+            &self,
+            text: &[u8],
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+        ) -> bool {
+            // Search from the start of the buffer, not from `begin`, so that
+            // a lookbehind assertion can see the text before `begin`; only
+            // report a match that actually starts at or after `begin`, same
+            // convention the onig and fancy-regex backends use.
+            match self.regex.captures_at(&text[..end], begin) {
+                Ok(Some(captures)) => {
+                    if let Some(region) = region {
+                        region.init_from_captures(&captures);
+                    }
+                    true
+                }
+                // Errors (including a hit match-time resource limit) are
+                // treated as non-matching, same as the other backends.
+                _ => false,
+            }
+        }
+
+        pub fn search_with_config(
+            &self,
+            text: &str,
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+            config: &super::MatchConfig,
+        ) -> Result<bool, super::MatchError> {
+            // PCRE2's match-time limit is set on the builder at compile time,
+            // not per search, so a configured `retry_limit` can't be honored
+            // here without recompiling.
+            let Some(time_limit) = config.time_limit else {
+                return match self.regex.captures_at(&text.as_bytes()[..end], begin) {
+                    Ok(Some(captures)) => {
+                        if let Some(region) = region {
+                            region.init_from_captures(&captures);
+                        }
+                        Ok(true)
+                    }
+                    Ok(None) => Ok(false),
+                    Err(_) => Err(super::MatchError),
+                };
+            };
+
+            // PCRE2 gives us no hook to interrupt a search in progress, so the
+            // only way to actually bound wall-clock time is to run the search
+            // on the shared worker pool and stop waiting on it once
+            // `time_limit` passes. If that happens, the job is abandoned: it
+            // keeps running to completion on its worker, but nobody's left
+            // waiting on the result.
+            let regex = Arc::clone(&self.regex);
+            let haystack = text.as_bytes()[..end].to_vec();
+            let (tx, rx) = mpsc::channel();
+            super::timeout_pool::run(move || {
+                let outcome = regex.captures_at(&haystack, begin).map(|found| {
+                    found.map(|captures| {
+                        (0..captures.len())
+                            .map(|i| captures.get(i).map(|m| (m.start(), m.end())))
+                            .collect::<Vec<_>>()
+                    })
+                });
+                // The receiver may already be gone if we missed the deadline;
+                // nothing to do about that.
+                let _ = tx.send(outcome);
+            });
+
+            match rx.recv_timeout(time_limit) {
+                Ok(Ok(Some(positions))) => {
+                    if let Some(region) = region {
+                        region.positions = positions;
+                    }
+                    Ok(true)
+                }
+                Ok(Ok(None)) => Ok(false),
+                Ok(Err(_)) | Err(_) => Err(super::MatchError),
+            }
+        }
+    }
+
+    impl Region {
+        fn init_from_captures(&mut self, captures: &pcre2::bytes::Captures) {
+            self.positions.clear();
+            for i in 0..captures.len() {
+                let pos = captures.get(i).map(|m| (m.start(), m.end()));
+                self.positions.push(pos);
+            }
+        }
+
+        pub fn pos(&self, i: usize) -> Option<(usize, usize)> {
+            self.positions.get(i).copied().flatten()
+        }
     }
 }
 
-// If both regex-fancy and regex-onig are requested, this condition makes regex-onig win.
-#[cfg(all(feature = "regex-fancy", not(feature = "regex-onig")))]
+// If both regex-fancy and either regex-onig or regex-pcre2 are requested,
+// this condition makes the others win.
+#[cfg(all(feature = "regex-fancy", not(feature = "regex-onig"), not(feature = "regex-pcre2")))]
 mod regex_impl {
     use std::error::Error;
 
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
     use smallvec::SmallVec;
 
     use crate::dumps::from_uncompressed_data;
 
     #[derive(Debug)]
     pub struct Regex {
-        regex: fancy_regex::Regex,
+        // `Arc`, not an owned `fancy_regex::Regex`, so `search_with_config`
+        // can hand a cheap clone off to the shared worker pool when
+        // enforcing `time_limit` (see its doc comment below).
+        regex: Arc<fancy_regex::Regex>,
     }
 
     #[derive(Clone, Debug, Eq, PartialEq)]
@@ -250,6 +1092,10 @@ mod regex_impl {
     }
 
     impl Regex {
+        pub fn new(pattern: &str) -> Result<Regex, Box<dyn Error + Send + Sync + 'static>> {
+            Ok(Self::from_expr_tree(Self::parse_expr_tree(pattern)?))
+        }
+
         pub fn parse_expr_tree(pattern: &str) -> Result<fancy_regex::ExprTree, Box<dyn Error + Send + Sync + 'static>> {
             Ok(fancy_regex::Expr::parse_tree(pattern)?)
         }
@@ -260,7 +1106,7 @@ mod regex_impl {
 
         pub fn from_expr_tree(expr_tree: fancy_regex::ExprTree) -> Regex {
             let regex = fancy_regex::RegexBuilder::new().build_from_expr_tree(expr_tree).unwrap();
-            Regex { regex }
+            Regex { regex: Arc::new(regex) }
         }
 
         pub fn is_match(&self, text: &str) -> bool {
@@ -287,6 +1133,87 @@ mod regex_impl {
                 false
             }
         }
+
+        pub fn search_with_config(
+            &self,
+            text: &str,
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+            config: &super::MatchConfig,
+        ) -> Result<bool, super::MatchError> {
+            // fancy-regex has no native retry-count limit, so `retry_limit` is
+            // ignored here.
+            let Some(time_limit) = config.time_limit else {
+                return match self.regex.captures_from_pos(&text[..end], begin) {
+                    Ok(Some(captures)) => {
+                        if let Some(region) = region {
+                            region.init_from_captures(&captures);
+                        }
+                        Ok(true)
+                    }
+                    Ok(None) => Ok(false),
+                    // For example, in case of catastrophic backtracking, fancy-regex
+                    // should fail with an error eventually.
+                    Err(_) => Err(super::MatchError),
+                };
+            };
+
+            // fancy-regex gives us no hook to interrupt a search in progress,
+            // so the only way to actually bound wall-clock time is to run the
+            // search on the shared worker pool and stop waiting on it once
+            // `time_limit` passes. If that happens, the job is abandoned: it
+            // keeps running to completion on its worker, but nobody's left
+            // waiting on the result.
+            let regex = Arc::clone(&self.regex);
+            let haystack = text[..end].to_owned();
+            let (tx, rx) = mpsc::channel();
+            super::timeout_pool::run(move || {
+                let outcome = regex.captures_from_pos(&haystack, begin).map(|found| {
+                    found.map(|captures| {
+                        (0..captures.len())
+                            .map(|i| captures.get(i).map(|m| (m.start(), m.end())))
+                            .collect::<SmallVec<[Option<(usize, usize)>; 8]>>()
+                    })
+                });
+                // The receiver may already be gone if we missed the deadline;
+                // nothing to do about that.
+                let _ = tx.send(outcome);
+            });
+
+            match rx.recv_timeout(time_limit) {
+                Ok(Ok(Some(positions))) => {
+                    if let Some(region) = region {
+                        region.positions = positions;
+                    }
+                    Ok(true)
+                }
+                Ok(Ok(None)) => Ok(false),
+                Ok(Err(_)) | Err(_) => Err(super::MatchError),
+            }
+        }
+
+        pub fn search_bytes(
+            &self,
+            text: &[u8],
+            begin: usize,
+            end: usize,
+            region: Option<&mut Region>,
+        ) -> bool {
+            // fancy-regex only understands `&str`, so non-UTF-8 input is
+            // viewed losslessly when it happens to be valid UTF-8, and
+            // through a lossy (replacement-character) conversion otherwise.
+            // In the lossy case, offsets past the first invalid byte may no
+            // longer line up exactly with `text`, since each invalid sequence
+            // is replaced by a differently-sized placeholder.
+            match std::str::from_utf8(text) {
+                Ok(text) => self.search(text, begin, end, region),
+                Err(_) => {
+                    let text = String::from_utf8_lossy(text);
+                    self.search(&text, begin, end, region)
+                }
+            }
+        }
     }
 
     impl Region {
@@ -320,4 +1247,148 @@ mod tests {
         assert!(regex.is_match("test"));
         assert!(regex.regex.get().is_some());
     }
+
+    #[test]
+    fn required_prefix_literals_of_a_plain_literal_pattern() {
+        let regex = Regex::new(String::from(r"foobar"));
+        assert_eq!(regex.required_prefix_literals(), Some(vec![String::from("foobar")]));
+    }
+
+    #[test]
+    fn required_prefix_literals_expands_a_leading_alternation() {
+        let regex = Regex::new(String::from(r"foo|bar"));
+        assert_eq!(
+            regex.required_prefix_literals(),
+            Some(vec![String::from("foo"), String::from("bar")])
+        );
+    }
+
+    #[test]
+    fn required_prefix_literals_is_none_for_an_unanchored_leading_class() {
+        // `.` can match anything, so there's no fixed literal every match of
+        // this pattern has to start with.
+        let regex = Regex::new(String::from(r".oo"));
+        assert_eq!(regex.required_prefix_literals(), None);
+    }
+
+    #[test]
+    fn required_prefix_literals_is_none_for_a_leading_lookaround() {
+        // A lookaround doesn't consume any characters itself, so it doesn't
+        // contribute to a fixed literal prefix either.
+        let regex = Regex::new(String::from(r"(?=foo)bar"));
+        assert_eq!(regex.required_prefix_literals(), None);
+    }
+
+    #[test]
+    fn regex_set_attributes_match_to_the_right_pattern_past_a_bracket_class() {
+        // `[()]` has no real capture group, even though it contains literal
+        // parentheses; if `count_capture_groups` miscounted them as two
+        // groups, the wrapper group computed for "bar" below would be wrong
+        // and the match would be attributed to the wrong pattern (or missed).
+        let set = RegexSet::new(vec![String::from(r"[()]"), String::from(r"bar")]);
+
+        let found = set.search_leftmost("xbar", 0, 4, None);
+        assert_eq!(found, Some((1, 1, 4)));
+    }
+
+    #[test]
+    fn regex_set_attributes_match_to_the_right_pattern_past_a_posix_bracket_class() {
+        // `[[:digit:](]` has no real capture group: the `]` ending the
+        // nested `[:digit:]` sub-expression isn't the one closing the outer
+        // class, so the literal `(` right after it is still inside the
+        // class, not a group.
+        let set = RegexSet::new(vec![String::from(r"[[:digit:](]"), String::from(r"bar")]);
+
+        let found = set.search_leftmost("xbar", 0, 4, None);
+        assert_eq!(found, Some((1, 1, 4)));
+    }
+
+    #[test]
+    fn regex_set_attributes_match_to_the_right_pattern_past_a_named_group() {
+        // `(?<name>foo)` is a capturing group; if it were miscounted as
+        // non-capturing, the wrapper group computed for "baz" below would be
+        // off by one and the match would be attributed to the wrong pattern.
+        let set = RegexSet::new(vec![String::from(r"(?<name>foo)"), String::from(r"baz")]);
+
+        let found = set.search_leftmost("xbaz", 0, 4, None);
+        assert_eq!(found, Some((1, 1, 4)));
+    }
+
+    #[test]
+    fn regex_set_search_leftmost_reuses_a_passed_in_region() {
+        let set = RegexSet::new(vec![String::from(r"foo"), String::from(r"bar")]);
+        let mut region = Region::new();
+
+        let found = set.search_leftmost("xbar", 0, 4, Some(&mut region));
+        assert_eq!(found, Some((1, 1, 4)));
+        assert_eq!(region.pos(0), Some((1, 4)));
+    }
+
+    #[cfg(all(feature = "regex-pcre2", not(feature = "regex-onig")))]
+    #[test]
+    fn pcre2_search_bytes_preserves_lookbehind_context() {
+        // If `search_bytes` sliced away everything before `begin` before
+        // matching, this lookbehind would never see the "foo" it depends on.
+        let regex = Regex::new(String::from(r"(?<=foo)bar"));
+        let text = b"foobar";
+
+        assert!(regex.search_bytes(text, 3, 6, None));
+    }
+
+    #[cfg(feature = "regex-onig")]
+    #[test]
+    fn onig_search_bytes_handles_invalid_utf8_without_panicking() {
+        // The 0xFF byte below is never valid UTF-8 on its own; searching
+        // over it must not reach for `str::from_utf8_unchecked`.
+        let regex = Regex::new(String::from(r"bar"));
+        let text = b"foo\xFFbar";
+
+        assert!(regex.search_bytes(text, 0, text.len(), None));
+    }
+
+    #[cfg(not(feature = "regex-onig"))]
+    #[test]
+    fn search_with_config_time_limit_bounds_wall_clock_time() {
+        // Without a worker-thread-based deadline, a catastrophic pattern
+        // like this one would block for however long backtracking actually
+        // takes; with one, `search_with_config` gives up within roughly
+        // `time_limit`, leaving the runaway search to finish on its own
+        // abandoned thread instead of blocking the caller.
+        let regex = Regex::new(String::from(r"(a+)+$"));
+        let text = "a".repeat(35) + "!";
+        let config = MatchConfig {
+            retry_limit: None,
+            time_limit: Some(Duration::from_millis(50)),
+        };
+
+        let start = std::time::Instant::now();
+        let result = regex.search_with_config(&text, 0, text.len(), None, &config);
+
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn compile_dfa_skips_patterns_with_capturing_groups() {
+        // A DFA-backed Region can only ever report group 0; a pattern with
+        // real capture groups must keep using the normal backend so
+        // `region.pos` on groups past 0 keeps working.
+        let regex = Regex::new(String::from(r"(foo)(bar)"));
+        let compiled = regex.compile_dfa();
+        let mut region = Region::new();
+
+        assert!(compiled.search("foobar", 0, 6, Some(&mut region)));
+        assert_eq!(region.pos(1), Some((0, 3)));
+        assert_eq!(region.pos(2), Some((3, 6)));
+    }
+
+    #[test]
+    fn compile_dfa_round_trips_through_a_dump() {
+        let regex = Regex::new(String::from(r"[a-z]+")).compile_dfa();
+        assert!(matches!(regex.source.as_ref(), RegexSource::Dfa(_)));
+
+        let mut region = Region::new();
+        assert!(regex.search("  hello  ", 0, 9, Some(&mut region)));
+        assert_eq!(region.pos(0), Some((2, 7)));
+    }
 }